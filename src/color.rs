@@ -1,16 +1,200 @@
 use std::fmt;
 
-/// Represents a color, holding red, green, blue and alpha values as `u8` each.
+/// Abstracts over the channel sample type a `Color` is built from, so the median cut pipeline can
+/// operate on 8-bit images (`u8`), 16-bit images (`u16`) and floating point HDR/EXR images (`f32`)
+/// without clamping everything to 8 bits up front.
+///
+pub trait Sample: Copy + Clone + fmt::Debug + PartialOrd {
+    /// Converts the sample to `f64` so generic code can do arithmetic (sums, means, differences)
+    /// independently of the concrete channel type.
+    fn as_f64(self) -> f64;
+
+    /// Builds a sample back from an `f64` produced by generic arithmetic, rounding or clamping as
+    /// appropriate for the concrete type.
+    fn from_f64(value: f64) -> Self;
+
+    /// Converts the sample down to an 8-bit channel value for final export.
+    fn to_u8(self) -> u8;
+}
+
+impl Sample for u8 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value.floor().clamp(0.0, u8::MAX as f64) as u8
+    }
+
+    fn to_u8(self) -> u8 {
+        self
+    }
+}
+
+impl Sample for u16 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value.floor().clamp(0.0, u16::MAX as f64) as u16
+    }
+
+    fn to_u8(self) -> u8 {
+        (self / 257) as u8
+    }
+}
+
+impl Sample for f32 {
+    fn as_f64(self) -> f64 {
+        self as f64
+    }
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+
+    fn to_u8(self) -> u8 {
+        (self.clamp(0.0, 1.0) * u8::MAX as f32).round() as u8
+    }
+}
+
+/// Represents a color, holding red, green, blue and alpha channel samples of type `T`.
+/// Defaults to `u8` samples, matching plain 8-bit-per-channel images.
 ///
 #[derive(Debug, Copy, Clone, PartialEq)]
-pub struct Color {
-    pub r: u8,
-    pub g: u8,
-    pub b: u8,
-    pub a: u8,
+pub struct Color<T = u8> {
+    pub r: T,
+    pub g: T,
+    pub b: T,
+    pub a: T,
+}
+
+impl<T: Sample> Color<T> {
+    /// Quantizes every channel down to `u8`, e.g. right before handing a palette built over `T`
+    /// to the (8-bit-only) `export` writers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let hdr_color = Color::<f32> { r: 1.0, g: 0.5, b: 0.0, a: 1.0 };
+    /// let color = hdr_color.quantize_to_u8();
+    /// assert_eq!(color, Color { r: 255, g: 128, b: 0, a: 255 });
+    /// ```
+    ///
+    pub fn quantize_to_u8(self) -> Color<u8> {
+        Color { r: self.r.to_u8(), g: self.g.to_u8(), b: self.b.to_u8(), a: self.a.to_u8() }
+    }
+}
+
+impl Color<u8> {
+    /// Parses a `#RRGGBB` (or bare `RRGGBB`) hex string into an opaque `Color`, i.e. alpha is
+    /// always set to 255. Returns `None` if `hex` isn't exactly 6 hex digits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// assert_eq!(Color::from_hex("#FF8000"), Some(Color { r: 255, g: 128, b: 0, a: 255 }));
+    /// ```
+    ///
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.strip_prefix('#').unwrap_or(hex);
+        if hex.len() != 6 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+
+        let r = u8::from_str_radix(&hex[0..2], 16).ok()?;
+        let g = u8::from_str_radix(&hex[2..4], 16).ok()?;
+        let b = u8::from_str_radix(&hex[4..6], 16).ok()?;
+
+        Some(Color { r, g, b, a: 255 })
+    }
+
+    /// Formats the color as an uppercase `#RRGGBB` hex string. Ignores alpha.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let color = Color { r: 255, g: 128, b: 0, a: 255 };
+    /// assert_eq!(color.to_hex(), "#FF8000");
+    /// ```
+    ///
+    pub fn to_hex(self) -> String {
+        format!("#{:02X}{:02X}{:02X}", self.r, self.g, self.b)
+    }
+
+    /// Packs the color into a 16-bit R5G5B5 value (5 bits per channel, alpha dropped), right
+    /// shifting each 8-bit channel by 3 to fit, as used by many retro image formats.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let color = Color { r: 255, g: 128, b: 0, a: 255 };
+    /// assert_eq!(color.to_r5g5b5(), 0b11111_10000_00000);
+    /// ```
+    ///
+    pub fn to_r5g5b5(self) -> u16 {
+        let r = (self.r >> 3) as u16;
+        let g = (self.g >> 3) as u16;
+        let b = (self.b >> 3) as u16;
+        (r << 10) | (g << 5) | b
+    }
+
+    /// Unpacks a 16-bit R5G5B5 value into an opaque 8-bit `Color`, left shifting each 5-bit field
+    /// by 3 to spread it back across the 0-255 range. Alpha is always set to 255.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let packed = 0b11111_10000_00000;
+    /// assert_eq!(Color::from_r5g5b5(packed), Color { r: 248, g: 128, b: 0, a: 255 });
+    /// ```
+    ///
+    pub fn from_r5g5b5(packed: u16) -> Self {
+        let r = ((packed >> 10) & 0x1F) as u8;
+        let g = ((packed >> 5) & 0x1F) as u8;
+        let b = (packed & 0x1F) as u8;
+
+        Color { r: r << 3, g: g << 3, b: b << 3, a: 255 }
+    }
+
+    /// Returns the color's complement, i.e. every channel subtracted from 255. Alpha is preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let color = Color { r: 255, g: 128, b: 0, a: 255 };
+    /// assert_eq!(color.complement(), Color { r: 0, g: 127, b: 255, a: 255 });
+    /// ```
+    ///
+    pub fn complement(self) -> Self {
+        Color { r: 255 - self.r, g: 255 - self.g, b: 255 - self.b, a: self.a }
+    }
+
+    /// Linearly interpolates between two colors. `t` is clamped to `[0.0, 1.0]`, where `0.0`
+    /// returns `a` and `1.0` returns `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let a = Color { r: 0, g: 0, b: 0, a: 255 };
+    /// let b = Color { r: 255, g: 255, b: 255, a: 255 };
+    /// assert_eq!(Color::lerp(a, b, 0.5), Color { r: 128, g: 128, b: 128, a: 255 });
+    /// ```
+    ///
+    pub fn lerp(a: Self, b: Self, t: f32) -> Self {
+        let t = t.clamp(0.0, 1.0);
+
+        Color {
+            r: (a.r as f32 + (b.r as f32 - a.r as f32) * t).round() as u8,
+            g: (a.g as f32 + (b.g as f32 - a.g as f32) * t).round() as u8,
+            b: (a.b as f32 + (b.b as f32 - a.b as f32) * t).round() as u8,
+            a: (a.a as f32 + (b.a as f32 - a.a as f32) * t).round() as u8,
+        }
+    }
 }
 
-impl fmt::Display for Color {
+impl<T: fmt::Display> fmt::Display for Color<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         write!(f, "{{ R: {}, G: {}, B: {}, A: {} }}", self.r, self.g, self.b, self.a)
     }
@@ -28,8 +212,8 @@ impl fmt::Display for Color {
 /// assert_eq!(4, color[ColorChannel::A]);
 /// ```
 ///
-impl ::std::ops::Index<ColorChannel> for Color {
-    type Output = u8;
+impl<T> ::std::ops::Index<ColorChannel> for Color<T> {
+    type Output = T;
     fn index(&self, index: ColorChannel) -> &Self::Output {
         match index {
             ColorChannel::R => &self.r,
@@ -62,4 +246,63 @@ mod tests {
         assert_eq!(3, color[ColorChannel::B]);
         assert_eq!(4, color[ColorChannel::A]);
     }
+
+    #[test]
+    fn quantize_to_u8_from_u16_ut() {
+        let color = Color::<u16> { r: 65535, g: 32896, b: 0, a: 65535 };
+        assert_eq!(color.quantize_to_u8(), Color { r: 255, g: 128, b: 0, a: 255 });
+    }
+
+    #[test]
+    fn quantize_to_u8_from_f32_ut() {
+        let color = Color::<f32> { r: 1.0, g: 0.5, b: 0.0, a: 1.0 };
+        assert_eq!(color.quantize_to_u8(), Color { r: 255, g: 128, b: 0, a: 255 });
+    }
+
+    #[test]
+    fn from_hex_ut() {
+        assert_eq!(Color::from_hex("#FF8000"), Some(Color { r: 255, g: 128, b: 0, a: 255 }));
+        assert_eq!(Color::from_hex("ff8000"), Some(Color { r: 255, g: 128, b: 0, a: 255 }));
+        assert_eq!(Color::from_hex("#ff80"), None);
+        assert_eq!(Color::from_hex("#gg8000"), None);
+    }
+
+    #[test]
+    fn to_hex_ut() {
+        let color = Color { r: 255, g: 128, b: 0, a: 255 };
+        assert_eq!(color.to_hex(), "#FF8000");
+    }
+
+    #[test]
+    fn r5g5b5_round_trip_ut() {
+        let color = Color { r: 255, g: 128, b: 0, a: 255 };
+        let packed = color.to_r5g5b5();
+        assert_eq!(packed, 0b11111_10000_00000);
+        assert_eq!(Color::from_r5g5b5(packed), Color { r: 248, g: 128, b: 0, a: 255 });
+    }
+
+    #[test]
+    fn complement_ut() {
+        let color = Color { r: 255, g: 128, b: 0, a: 200 };
+        assert_eq!(color.complement(), Color { r: 0, g: 127, b: 255, a: 200 });
+    }
+
+    #[test]
+    fn lerp_ut() {
+        let a = Color { r: 0, g: 0, b: 0, a: 255 };
+        let b = Color { r: 255, g: 255, b: 255, a: 255 };
+
+        assert_eq!(Color::lerp(a, b, 0.0), a);
+        assert_eq!(Color::lerp(a, b, 1.0), b);
+        assert_eq!(Color::lerp(a, b, 0.5), Color { r: 128, g: 128, b: 128, a: 255 });
+    }
+
+    #[test]
+    fn lerp_clamps_t_ut() {
+        let a = Color { r: 0, g: 0, b: 0, a: 255 };
+        let b = Color { r: 255, g: 255, b: 255, a: 255 };
+
+        assert_eq!(Color::lerp(a, b, -1.0), a);
+        assert_eq!(Color::lerp(a, b, 2.0), b);
+    }
 }
@@ -1,17 +1,71 @@
-use crate::utils::mean;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::collections::HashMap;
+
+use image::ColorType;
+
+use crate::color::Sample;
+use crate::utils::weighted_mean;
+use crate::utils::weighted_mean_f64;
 use crate::Color;
 use crate::ColorChannel;
 
-/// Struct holding an `Vec<Color>`.
+/// Struct holding a deduplicated `Vec<Color<T>>` and a parallel `Vec<u32>` of how often each color
+/// occurs in the source pixels.
 /// Implements helpful functions for the median cut algorithm.
 ///
+/// Deduplicating up front means buckets over images with large flat regions shrink to their
+/// distinct colors immediately, and every statistic below (mean, median, variance) is weighted by
+/// `counts` so that populous colors are not drowned out by many rarer ones.
+///
+/// Generic over the channel sample type `T` (see `color::Sample`), defaulting to `u8` for plain
+/// 8-bit-per-channel images.
+///
 #[derive(Debug, PartialEq)]
-pub struct ColorBucket {
-    colors: Vec<Color>,
+pub struct ColorBucket<T = u8> {
+    colors: Vec<Color<T>>,
+    counts: Vec<u32>,
 }
 
-impl ColorBucket {
-    /// Creates a ColorBucket based on the colors passed. Returns `None` if passed an empty vector.
+/// Wraps a `ColorBucket` with its split priority so it can be ordered in a max-heap.
+/// Priority is the bucket's highest per-channel weighted variance multiplied by its pixel count,
+/// so populous, high-variance buckets are split before small or near-uniform ones.
+///
+struct SplitCandidate<T> {
+    priority: u64,
+    bucket: ColorBucket<T>,
+}
+
+impl<T: Sample> SplitCandidate<T> {
+    fn new(bucket: ColorBucket<T>) -> Self {
+        let priority = bucket.split_priority();
+        Self { priority, bucket }
+    }
+}
+
+impl<T> PartialEq for SplitCandidate<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.priority == other.priority
+    }
+}
+
+impl<T> Eq for SplitCandidate<T> {}
+
+impl<T> PartialOrd for SplitCandidate<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<T> Ord for SplitCandidate<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.priority.cmp(&other.priority)
+    }
+}
+
+impl<T: Sample> ColorBucket<T> {
+    /// Creates a ColorBucket based on the colors passed, deduplicating equal colors into a single
+    /// `(Color, count)` entry. Returns `None` if passed an empty vector.
     ///
     /// # Arguments
     ///
@@ -25,165 +79,268 @@ impl ColorBucket {
     /// assert_eq!(result.colors, data);
     /// ```
     ///
-    pub fn from_pixels(pixels: Vec<Color>) -> Option<Self> {
-        if pixels.is_empty() {
+    pub fn from_pixels(pixels: Vec<Color<T>>) -> Option<Self> {
+        let mut index_by_key: HashMap<(u64, u64, u64, u64), usize> = HashMap::new();
+        let mut colors = vec![];
+        let mut counts = vec![];
+
+        for pixel in pixels {
+            match index_by_key.get(&Self::dedup_key(pixel)) {
+                Some(&index) => counts[index] += 1,
+                None => {
+                    index_by_key.insert(Self::dedup_key(pixel), colors.len());
+                    colors.push(pixel);
+                    counts.push(1u32);
+                }
+            }
+        }
+
+        Self::from_deduped(colors, counts)
+    }
+
+    /// Creates a ColorBucket from already-deduplicated `colors` and their parallel `counts`.
+    /// Returns `None` if passed an empty vector.
+    ///
+    fn from_deduped(colors: Vec<Color<T>>, counts: Vec<u32>) -> Option<Self> {
+        if colors.is_empty() {
             None
         } else {
-            Some(Self { colors: pixels })
+            Some(Self { colors, counts })
         }
     }
 
-    /// Recursivly performs the median cut algorithm on self if iteration has not reached 0 yet.
-    /// Creates two new buckets based on own colors. One bucket with values above and one bucket with value below the median, then performs the algorithm on them again.
+    /// Returns a hashable key identifying a color's exact channel values, used to deduplicate
+    /// pixels in [`ColorBucket::from_pixels`] regardless of whether `T` itself implements `Eq`/`Hash`.
+    ///
+    fn dedup_key(color: Color<T>) -> (u64, u64, u64, u64) {
+        (color.r.as_f64().to_bits(), color.g.as_f64().to_bits(), color.b.as_f64().to_bits(), color.a.as_f64().to_bits())
+    }
+
+    /// Returns the total number of (possibly repeated) pixels represented by this bucket.
+    ///
+    fn total_count(&self) -> u64 {
+        self.counts.iter().map(|&count| count as u64).sum()
+    }
+
+    /// Creates a color palette of `2.pow(iter_count)` colors from own pixels.
     ///
-    /// If iteration has reached 0 the color mean for self is pushed to the result vector.
+    /// Thin wrapper around [`ColorBucket::make_palette_sized`] kept for backwards compatibility
+    /// with the old fixed-depth recursion, which could only ever produce a power-of-two palette size.
     ///
     /// # Arguments
     ///
-    /// * `iter_count` - Iteration index is used as termination criteria. Recursion stop when 0 is reached.
-    /// * `result` - Vector holding color means for each bucket in the iteration.
+    /// * `iter_count` - number of times every bucket is split, giving `2.pow(iter_count)` colors.
     ///
-    fn recurse(&mut self, iter_count: u8, result: &mut Vec<Color>) {
-        if iter_count == 0 {
-            result.push(self.color_mean())
-        } else {
-            let new_buckets = self.median_cut();
-            if let Some(mut bucket) = new_buckets.0 {
-                bucket.recurse(iter_count - 1, result);
-            }
-            if let Some(mut bucket) = new_buckets.1 {
-                bucket.recurse(iter_count - 1, result)
-            }
-        }
+    /// # Example
+    ///
+    /// ```
+    /// let data = vec![Color { r: 15, g: 131, b: 0, a: 255 }, Color { r: 221, g: 11, b: 22, a: 130 }, Color { r: 81, g: 11, b: 16, a: 0 }];
+    /// let mut bucket = ColorBucket::from_pixels(data.clone()).expect("Passed empty color vector to test.");
+    /// let result = bucket.make_palette(2);
+    /// ```
+    ///
+    pub fn make_palette(&mut self, iter_count: u8) -> Vec<Color<T>> {
+        self.make_palette_sized(1usize << iter_count)
     }
 
-    /// Creates a color palette from own pixels.
+    /// Creates a color palette holding exactly `k` colors (or fewer, if there are fewer than `k`
+    /// distinct colors available) using a best-first median cut.
+    ///
+    /// Repeatedly pops the bucket with the highest split priority (see [`SplitCandidate`]) off a
+    /// max-heap, splits it with [`ColorBucket::median_cut`] and pushes its children back, until the
+    /// heap holds `k` buckets or no remaining bucket can be split any further.
     ///
     /// # Arguments
     ///
-    /// * `iter_count` - number of iterations to be performed on the bucket.
+    /// * `k` - desired number of colors in the resulting palette.
     ///
     /// # Example
     ///
     /// ```
     /// let data = vec![Color { r: 15, g: 131, b: 0, a: 255 }, Color { r: 221, g: 11, b: 22, a: 130 }, Color { r: 81, g: 11, b: 16, a: 0 }];
-    /// let bucket = ColorBucket::from_pixels(data.clone()).expect("Passed empty color vector to test.");
-    /// let result = bucket.make_palette();
+    /// let mut bucket = ColorBucket::from_pixels(data.clone()).expect("Passed empty color vector to test.");
+    /// let result = bucket.make_palette_sized(2);
     /// ```
     ///
-    pub fn make_palette(&mut self, iter_count: u8) -> Vec<Color> {
-        let mut result = vec![];
-        self.recurse(iter_count, &mut result);
-        result
+    pub fn make_palette_sized(&mut self, k: usize) -> Vec<Color<T>> {
+        if k == 0 {
+            return vec![];
+        }
+
+        let seed = ColorBucket { colors: self.colors.clone(), counts: self.counts.clone() };
+
+        let mut heap: BinaryHeap<SplitCandidate<T>> = BinaryHeap::new();
+        let mut terminal: Vec<ColorBucket<T>> = vec![];
+
+        if seed.is_terminal() {
+            terminal.push(seed);
+        } else {
+            heap.push(SplitCandidate::new(seed));
+        }
+
+        while heap.len() + terminal.len() < k {
+            let candidate = match heap.pop() {
+                Some(candidate) => candidate,
+                None => break,
+            };
+
+            let mut bucket = candidate.bucket;
+            let original_len = bucket.colors.len();
+            let (above, below) = bucket.median_cut();
+
+            for child in [above, below].into_iter().flatten() {
+                // `median_cut` keeps a color on each side of a bucket with two or more colors; a
+                // child still holding every color of its parent would mean the split made no
+                // progress, so treat it as terminal too rather than re-split it forever.
+                if child.colors.len() == original_len || child.is_terminal() {
+                    terminal.push(child);
+                } else {
+                    heap.push(SplitCandidate::new(child));
+                }
+            }
+        }
+
+        heap.into_iter().map(|candidate| candidate.bucket).chain(terminal).map(|bucket| bucket.color_mean()).collect()
+    }
+
+    /// Returns `true` if the bucket cannot be split any further, i.e. it holds at most one
+    /// distinct color or every color channel (ignoring alpha) already has zero weighted variance
+    /// across its pixels.
+    ///
+    fn is_terminal(&self) -> bool {
+        self.colors.len() <= 1 || self.highest_variance() == 0.0
+    }
+
+    /// Returns this bucket's split priority: its highest per-channel weighted variance multiplied
+    /// by its (weighted) pixel count, so populous, high-variance buckets are preferred by
+    /// [`ColorBucket::make_palette_sized`].
+    ///
+    fn split_priority(&self) -> u64 {
+        (self.highest_variance() * self.total_count() as f64) as u64
+    }
+
+    /// Returns the highest weighted variance among the R, G and B channels. Ignores alpha,
+    /// consistent with [`ColorBucket::highest_variance_channel`].
+    ///
+    fn highest_variance(&self) -> f64 {
+        let r = self.channel_variance(ColorChannel::R);
+        let g = self.channel_variance(ColorChannel::G);
+        let b = self.channel_variance(ColorChannel::B);
+        r.max(g).max(b)
     }
 
     /// Performs the median cut on a own vector (bucket) of `Color`.
     /// Returns two `Color` vectors representing the colors above and colors below median value.
     ///
-    fn median_cut(&mut self) -> (Option<ColorBucket>, Option<ColorBucket>) {
-        let highest_range_channel = self.highest_range_channel();
-        let median = self.color_median(highest_range_channel);
-        let mut above_median = vec![];
-        let mut below_median = vec![];
-        for color in &self.colors {
-            if color[highest_range_channel] > median {
-                above_median.push(*color);
-            } else {
-                below_median.push(*color)
-            }
-        }
+    fn median_cut(&mut self) -> (Option<ColorBucket<T>>, Option<ColorBucket<T>>) {
+        let highest_variance_channel = self.highest_variance_channel();
+        // Cap the cut so a bucket of two or more colors always keeps at least one color on each
+        // side, even when its last color alone holds half or more of the total count.
+        let split_index = self.weighted_median_index(highest_variance_channel).min(self.colors.len().saturating_sub(2));
+
+        let below_colors = self.colors[..=split_index].to_vec();
+        let below_counts = self.counts[..=split_index].to_vec();
+        let above_colors = self.colors[split_index + 1..].to_vec();
+        let above_counts = self.counts[split_index + 1..].to_vec();
 
-        (ColorBucket::from_pixels(above_median), ColorBucket::from_pixels(below_median))
+        (ColorBucket::from_deduped(above_colors, above_counts), ColorBucket::from_deduped(below_colors, below_counts))
     }
 
-    /// Returns the color channel with the highest range.
+    /// Returns the color channel with the highest weighted variance.
     /// IMPORTANT: Ignores alpha channel!
     ///
-    fn highest_range_channel(&self) -> ColorChannel {
-        let ranges = self.color_ranges();
-        let mut highest_range_channel = ColorChannel::R;
-        let mut highest_value = ranges.r;
+    fn highest_variance_channel(&self) -> ColorChannel {
+        let r = self.channel_variance(ColorChannel::R);
+        let g = self.channel_variance(ColorChannel::G);
+        let b = self.channel_variance(ColorChannel::B);
+
+        let mut highest_variance_channel = ColorChannel::R;
+        let mut highest_value = r;
 
-        if ranges.g > highest_value {
-            highest_range_channel = ColorChannel::G;
-            highest_value = ranges.g;
+        if g > highest_value {
+            highest_variance_channel = ColorChannel::G;
+            highest_value = g;
         }
 
-        if ranges.b > highest_value {
-            highest_range_channel = ColorChannel::B;
+        if b > highest_value {
+            highest_variance_channel = ColorChannel::B;
         }
 
-        highest_range_channel
+        highest_variance_channel
     }
 
-    /// Returns the ranges for each color channel.
+    /// Returns the population-weighted variance of a single color channel across `colors`,
+    /// i.e. `Σcount·(value - weighted_mean)² / Σcount`.
     ///
-    /// # Examples
+    /// # Panics
     ///
-    fn color_ranges(&self) -> Color {
-        // Unwrap is ok here, because `max_by_key` only returns `None` for empty vectors
-        Color {
-            r: self.colors.iter().max_by_key(|c| c.r).unwrap().r - self.colors.iter().min_by_key(|c| c.r).unwrap().r,
-            g: self.colors.iter().max_by_key(|c| c.g).unwrap().g - self.colors.iter().min_by_key(|c| c.g).unwrap().g,
-            b: self.colors.iter().max_by_key(|c| c.b).unwrap().b - self.colors.iter().min_by_key(|c| c.b).unwrap().b,
-            a: self.colors.iter().max_by_key(|c| c.a).unwrap().a - self.colors.iter().min_by_key(|c| c.a).unwrap().a,
-        }
+    /// Panics if `colors` is empty.
+    ///
+    fn channel_variance(&self, channel: ColorChannel) -> f64 {
+        let mean = weighted_mean_f64(self.colors.iter().zip(&self.counts).map(|(color, &count)| (color[channel], count)));
+        let total = self.total_count() as f64;
+
+        let weighted_squared_deviation: f64 = self
+            .colors
+            .iter()
+            .zip(&self.counts)
+            .map(|(color, &count)| {
+                let deviation = color[channel].as_f64() - mean;
+                deviation * deviation * count as f64
+            })
+            .sum();
+
+        weighted_squared_deviation / total
     }
 
-    /// Sort a colors for a specific channel.
+    /// Sorts the bucket's colors (and their parallel counts) by a specific channel.
     ///
     /// # Arguments
     ///
     /// * `channel` - Target channel. The sorting is performed based on this value.
     ///
-    /// # Examples
-    ///
     fn sort_colors(&mut self, channel: ColorChannel) {
-        self.colors.sort_by_key(|x| x[channel])
+        let mut paired: Vec<(Color<T>, u32)> = self.colors.drain(..).zip(self.counts.drain(..)).collect();
+        paired.sort_by(|a, b| a.0[channel].partial_cmp(&b.0[channel]).unwrap_or(Ordering::Equal));
+
+        for (color, count) in paired {
+            self.colors.push(color);
+            self.counts.push(count);
+        }
     }
 
-    /// Returns median value for a specific `ColorChannel`.
-    ///
-    /// # Arguments
-    ///
-    /// * `channel` - Target channel for which the median is calculated.
+    /// Sorts the bucket by `channel` and returns the index at which the cumulative (weighted)
+    /// count first reaches half the bucket's total count. Used to cut the bucket at that position
+    /// ([`ColorBucket::median_cut`]), so a tie at the halfway point (e.g. two equally populous
+    /// colors) always yields a real split instead of landing both colors on the same side.
     ///
-    fn color_median(&mut self, channel: ColorChannel) -> u8 {
+    fn weighted_median_index(&mut self, channel: ColorChannel) -> usize {
         self.sort_colors(channel);
 
-        let mid = self.colors.len() / 2;
-        if self.colors.len() % 2 == 0 {
-            let bucket = ColorBucket::from_pixels(vec![self.colors[mid - 1], self.colors[mid]]).unwrap();
-            bucket.channel_mean(channel)
-        } else {
-            self.channel_value_by_index(mid, channel)
+        let half = self.total_count() as f64 / 2.0;
+        let mut cumulative = 0u64;
+
+        for (index, &count) in self.counts.iter().enumerate() {
+            cumulative += count as u64;
+            if cumulative as f64 >= half {
+                return index;
+            }
         }
-    }
 
-    /// Returns a color value based on the provided channel and index parameters.
-    ///
-    /// # Arguments
-    ///
-    /// * `index` - Index of the target color in the vector.
-    /// * `channel` - Color channel of the searched value.
-    ///
-    fn channel_value_by_index(&self, index: usize, channel: ColorChannel) -> u8 {
-        self.colors[index][channel]
+        self.colors.len() - 1
     }
 
-    /// Calculate the mean value for a specific color channel on own vector of `Color`.
+    /// Calculate the population-weighted mean value for a specific color channel on own colors.
     ///
     /// # Arguments
     ///
     /// * `channel` - Target channel for which the mean is calculated.
     ///
-    /// # Examples
-    ///
-    fn channel_mean(&self, channel: ColorChannel) -> u8 {
-        mean(self.colors.iter().map(|x| x[channel]))
+    fn channel_mean(&self, channel: ColorChannel) -> T {
+        weighted_mean(self.colors.iter().zip(&self.counts).map(|(color, &count)| (color[channel], count)))
     }
 
-    /// Returns the mean color value based on own colors.
+    /// Returns the population-weighted mean color value based on own colors.
     ///
     /// # Examples
     ///
@@ -192,43 +349,102 @@ impl ColorBucket {
     /// let result = color_mean(&colors);
     /// ```
     ///
-    fn color_mean(&self) -> Color {
-        let r = mean(self.colors.iter().map(|c| c.r));
-        let g = mean(self.colors.iter().map(|c| c.g));
-        let b = mean(self.colors.iter().map(|c| c.b));
-        let a = mean(self.colors.iter().map(|c| c.a));
+    fn color_mean(&self) -> Color<T> {
+        let r = self.channel_mean(ColorChannel::R);
+        let g = self.channel_mean(ColorChannel::G);
+        let b = self.channel_mean(ColorChannel::B);
+        let a = self.channel_mean(ColorChannel::A);
 
         Color { r, g, b, a }
     }
 }
 
+/// Wraps a `ColorBucket` over whichever `Sample` type best represents an image's native bit
+/// depth, so callers that only know the file path at runtime (e.g. the CLI) aren't forced to
+/// clamp 16-bit or floating point sources down to `u8` before quantizing.
+///
+pub enum AnyColorBucket {
+    U8(ColorBucket<u8>),
+    U16(ColorBucket<u16>),
+    F32(ColorBucket<f32>),
+}
+
+impl AnyColorBucket {
+    /// Opens `image_file_path` and builds a `ColorBucket` over its native sample type: `u16` for
+    /// 16-bit-per-channel images, `f32` for floating point HDR images, and `u8` for everything
+    /// else. Returns `None` if the image can't be opened/decoded or contains no pixels.
+    ///
+    /// # Arguments
+    ///
+    /// * `image_file_path` - Path of the image to load.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let bucket = AnyColorBucket::from_image("example.jpg");
+    /// ```
+    ///
+    pub fn from_image(image_file_path: &str) -> Option<Self> {
+        let image = image::open(image_file_path).ok()?;
+
+        match image.color() {
+            ColorType::Rgb32F | ColorType::Rgba32F => {
+                let pixels = image.to_rgba32f().pixels().map(|p| Color { r: p[0], g: p[1], b: p[2], a: p[3] }).collect();
+                ColorBucket::from_pixels(pixels).map(AnyColorBucket::F32)
+            }
+            ColorType::L16 | ColorType::La16 | ColorType::Rgb16 | ColorType::Rgba16 => {
+                let pixels = image.to_rgba16().pixels().map(|p| Color { r: p[0], g: p[1], b: p[2], a: p[3] }).collect();
+                ColorBucket::from_pixels(pixels).map(AnyColorBucket::U16)
+            }
+            _ => {
+                let pixels = image.to_rgba8().pixels().map(|p| Color { r: p[0], g: p[1], b: p[2], a: p[3] }).collect();
+                ColorBucket::from_pixels(pixels).map(AnyColorBucket::U8)
+            }
+        }
+    }
+
+    /// Creates a color palette of `2.pow(iter_count)` colors from own pixels, quantized to `u8`
+    /// for export regardless of the bucket's native sample type.
+    ///
+    pub fn make_palette(&mut self, iter_count: u8) -> Vec<Color<u8>> {
+        match self {
+            AnyColorBucket::U8(bucket) => bucket.make_palette(iter_count),
+            AnyColorBucket::U16(bucket) => bucket.make_palette(iter_count).into_iter().map(Color::quantize_to_u8).collect(),
+            AnyColorBucket::F32(bucket) => bucket.make_palette(iter_count).into_iter().map(Color::quantize_to_u8).collect(),
+        }
+    }
+
+    /// Creates a color palette holding exactly `num_colors` colors (or fewer, if there are fewer
+    /// than `num_colors` distinct colors available), quantized to `u8` for export regardless of
+    /// the bucket's native sample type.
+    ///
+    pub fn make_palette_sized(&mut self, num_colors: usize) -> Vec<Color<u8>> {
+        match self {
+            AnyColorBucket::U8(bucket) => bucket.make_palette_sized(num_colors),
+            AnyColorBucket::U16(bucket) => bucket.make_palette_sized(num_colors).into_iter().map(Color::quantize_to_u8).collect(),
+            AnyColorBucket::F32(bucket) => bucket.make_palette_sized(num_colors).into_iter().map(Color::quantize_to_u8).collect(),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
     #[test]
     fn from_pixels_ut() {
-        let bucket = ColorBucket::from_pixels(vec![]);
+        let bucket: Option<ColorBucket> = ColorBucket::from_pixels(vec![]);
         assert_eq!(bucket, None);
 
         let data = vec![Color { r: 15, g: 131, b: 0, a: 255 }, Color { r: 221, g: 11, b: 22, a: 130 }, Color { r: 81, g: 11, b: 16, a: 0 }];
-        let bucket = ColorBucket::from_pixels(data.clone()).expect("Passed empty color vector to test.");
+        let bucket: ColorBucket = ColorBucket::from_pixels(data.clone()).expect("Passed empty color vector to test.");
         assert_eq!(bucket.colors, data);
     }
 
-    #[test]
-    fn recurse_ut() {
-        let pixels = vec![Color { r: 255, g: 0, b: 0, a: 255 }, Color { r: 0, g: 255, b: 0, a: 255 }];
-        let mut bucket = ColorBucket::from_pixels(pixels.clone()).expect("Passed empty color vector to test.");
-        let mut result = vec![];
-        bucket.recurse(1, &mut result);
-        assert_eq!(result, pixels);
-    }
-
     #[test]
     fn make_palette_ut() {
         let pixels = vec![Color { r: 100, g: 120, b: 120, a: 0 }, Color { r: 150, g: 150, b: 150, a: 0 }, Color { r: 255, g: 255, b: 255, a: 0 }];
-        let mut bucket = ColorBucket::from_pixels(pixels.clone()).expect("Passed empty color vector to test.");
+        let mut bucket: ColorBucket = ColorBucket::from_pixels(pixels.clone()).expect("Passed empty color vector to test.");
 
         let colors = bucket.make_palette(3);
         let expected = vec![Color { r: 255, g: 255, b: 255, a: 0 }, Color { r: 150, g: 150, b: 150, a: 0 }, Color { r: 100, g: 120, b: 120, a: 0 }];
@@ -236,55 +452,56 @@ mod tests {
     }
 
     #[test]
-    pub fn sort_colors_ut() {
-        let colors = generate_unsorted_colors();
-        let mut bucket = ColorBucket::from_pixels(colors.clone()).expect("Passed empty color vector to test");
-        bucket.sort_colors(ColorChannel::R);
+    fn make_palette_sized_exact_count_ut() {
+        let pixels = vec![Color { r: 255, g: 0, b: 0, a: 255 }, Color { r: 0, g: 255, b: 0, a: 255 }];
+        let mut bucket: ColorBucket = ColorBucket::from_pixels(pixels.clone()).expect("Passed empty color vector to test.");
 
-        assert_eq!(bucket.colors[0], Color { r: 0, g: 2, b: 1, a: 20 });
-        assert_eq!(bucket.colors[1], Color { r: 1, g: 23, b: 16, a: 20 });
-        assert_eq!(bucket.colors[2], Color { r: 3, g: 4, b: 15, a: 2 });
-        assert_eq!(bucket.colors[3], Color { r: 55, g: 17, b: 0, a: 118 });
+        let colors = bucket.make_palette_sized(2);
+        assert_eq!(colors.len(), 2);
+        assert!(colors.contains(&pixels[0]));
+        assert!(colors.contains(&pixels[1]));
     }
 
     #[test]
-    pub fn color_median_ut() {
-        let colors = generate_unsorted_colors();
-        let mut bucket = ColorBucket::from_pixels(colors.clone()).expect("Passed empty color vector to test");
-        let result = bucket.color_median(ColorChannel::R);
-        assert_eq!(result, 2);
+    fn make_palette_sized_zero_ut() {
+        let pixels = vec![Color { r: 255, g: 0, b: 0, a: 255 }, Color { r: 0, g: 255, b: 0, a: 255 }];
+        let mut bucket: ColorBucket = ColorBucket::from_pixels(pixels).expect("Passed empty color vector to test.");
+
+        let colors = bucket.make_palette_sized(0);
+        assert_eq!(colors, vec![]);
     }
 
     #[test]
-    fn channel_value_by_index_ut() {
-        let colors = vec![
-            Color { r: 100, g: 22, b: 12, a: 0 },
-            Color { r: 126, g: 175, b: 137, a: 1 },
-            Color { r: 221, g: 225, b: 0, a: 113 },
-            Color { r: 13, g: 226, b: 0, a: 17 },
-        ];
+    fn make_palette_sized_exceeds_distinct_colors_ut() {
+        let pixels = vec![Color { r: 255, g: 255, b: 255, a: 255 }, Color { r: 0, g: 0, b: 0, a: 255 }];
+        let mut bucket: ColorBucket = ColorBucket::from_pixels(pixels).expect("Passed empty color vector to test.");
 
-        let bucket = ColorBucket::from_pixels(colors).expect("Passing empty color vector to test");
+        let colors = bucket.make_palette_sized(10);
+        let expected = vec![Color { r: 255, g: 255, b: 255, a: 255 }, Color { r: 0, g: 0, b: 0, a: 255 }];
+        assert_eq!(colors, expected);
+    }
 
-        assert_eq!(100, bucket.channel_value_by_index(0, ColorChannel::R));
-        assert_eq!(22, bucket.channel_value_by_index(0, ColorChannel::G));
-        assert_eq!(12, bucket.channel_value_by_index(0, ColorChannel::B));
-        assert_eq!(0, bucket.channel_value_by_index(0, ColorChannel::A));
+    #[test]
+    fn make_palette_sized_f32_samples_ut() {
+        let pixels = vec![Color::<f32> { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }, Color::<f32> { r: 1.0, g: 1.0, b: 1.0, a: 1.0 }];
+        let mut bucket = ColorBucket::from_pixels(pixels.clone()).expect("Passed empty color vector to test.");
 
-        assert_eq!(126, bucket.channel_value_by_index(1, ColorChannel::R));
-        assert_eq!(175, bucket.channel_value_by_index(1, ColorChannel::G));
-        assert_eq!(137, bucket.channel_value_by_index(1, ColorChannel::B));
-        assert_eq!(1, bucket.channel_value_by_index(1, ColorChannel::A));
+        let colors = bucket.make_palette_sized(2);
+        assert_eq!(colors.len(), 2);
+        assert!(colors.contains(&pixels[0]));
+        assert!(colors.contains(&pixels[1]));
+    }
 
-        assert_eq!(221, bucket.channel_value_by_index(2, ColorChannel::R));
-        assert_eq!(225, bucket.channel_value_by_index(2, ColorChannel::G));
-        assert_eq!(0, bucket.channel_value_by_index(2, ColorChannel::B));
-        assert_eq!(113, bucket.channel_value_by_index(2, ColorChannel::A));
+    #[test]
+    pub fn sort_colors_ut() {
+        let colors = generate_unsorted_colors();
+        let mut bucket = ColorBucket::from_pixels(colors.clone()).expect("Passed empty color vector to test");
+        bucket.sort_colors(ColorChannel::R);
 
-        assert_eq!(13, bucket.channel_value_by_index(3, ColorChannel::R));
-        assert_eq!(226, bucket.channel_value_by_index(3, ColorChannel::G));
-        assert_eq!(0, bucket.channel_value_by_index(3, ColorChannel::B));
-        assert_eq!(17, bucket.channel_value_by_index(3, ColorChannel::A));
+        assert_eq!(bucket.colors[0], Color { r: 0, g: 2, b: 1, a: 20 });
+        assert_eq!(bucket.colors[1], Color { r: 1, g: 23, b: 16, a: 20 });
+        assert_eq!(bucket.colors[2], Color { r: 3, g: 4, b: 15, a: 2 });
+        assert_eq!(bucket.colors[3], Color { r: 55, g: 17, b: 0, a: 118 });
     }
 
     #[test]
@@ -296,7 +513,7 @@ mod tests {
             Color { r: 100, g: 50, b: 12, a: 255 },
         ];
 
-        let bucket = ColorBucket::from_pixels(colors).expect("Passed empty color vector to test.");
+        let bucket: ColorBucket = ColorBucket::from_pixels(colors).expect("Passed empty color vector to test.");
         let mut result = bucket.channel_mean(ColorChannel::R);
         assert_eq!(100, result);
         result = bucket.channel_mean(ColorChannel::G);
@@ -314,7 +531,7 @@ mod tests {
             Color { r: 13, g: 226, b: 0, a: 17 },
         ];
 
-        let bucket = ColorBucket::from_pixels(colors).expect("Passed empty color vector to test.");
+        let bucket: ColorBucket = ColorBucket::from_pixels(colors).expect("Passed empty color vector to test.");
 
         result = bucket.channel_mean(ColorChannel::R);
         assert_eq!(115, result);
@@ -349,26 +566,44 @@ mod tests {
             Some(ColorBucket::from_pixels(vec![Color { r: 0, g: 2, b: 1, a: 20 }, Color { r: 1, g: 23, b: 16, a: 20 }]).unwrap())
         );
 
-        let mut bucket = ColorBucket::from_pixels(vec![Color { r: 0, g: 0, b: 0, a: 0 }]).expect("Passed empty color vector to test.");
+        let mut bucket: ColorBucket = ColorBucket::from_pixels(vec![Color { r: 0, g: 0, b: 0, a: 0 }]).expect("Passed empty color vector to test.");
         let result = bucket.median_cut();
         assert_eq!(result.0, None);
         assert_eq!(result.1, Some(ColorBucket::from_pixels(vec![Color { r: 0, g: 0, b: 0, a: 0 }])).unwrap());
     }
 
     #[test]
-    fn highest_range_channel_ut() {
+    fn highest_variance_channel_ut() {
         let bucket = ColorBucket::from_pixels(generate_unsorted_colors()).expect("Passed empty color vector to test");
-        assert_eq!(ColorChannel::R, bucket.highest_range_channel());
-        assert_ne!(ColorChannel::G, bucket.highest_range_channel());
-        assert_ne!(ColorChannel::B, bucket.highest_range_channel());
-        assert_ne!(ColorChannel::A, bucket.highest_range_channel());
+        assert_eq!(ColorChannel::R, bucket.highest_variance_channel());
+        assert_ne!(ColorChannel::G, bucket.highest_variance_channel());
+        assert_ne!(ColorChannel::B, bucket.highest_variance_channel());
+        assert_ne!(ColorChannel::A, bucket.highest_variance_channel());
     }
 
     #[test]
-    fn color_ranges_ut() {
+    fn channel_variance_ut() {
         let bucket = ColorBucket::from_pixels(generate_unsorted_colors()).expect("Passed empty color vector to test");
-        let expected = Color { r: 55, g: 21, b: 16, a: 116 };
-        assert_eq!(expected, bucket.color_ranges());
+        assert_eq!(541.1875, bucket.channel_variance(ColorChannel::R));
+        assert_eq!(77.25, bucket.channel_variance(ColorChannel::G));
+        assert_eq!(56.5, bucket.channel_variance(ColorChannel::B));
+    }
+
+    #[test]
+    fn from_pixels_dedupes_repeated_colors_ut() {
+        let pixels = vec![Color { r: 10, g: 10, b: 10, a: 255 }, Color { r: 10, g: 10, b: 10, a: 255 }, Color { r: 200, g: 0, b: 0, a: 255 }];
+        let bucket: ColorBucket = ColorBucket::from_pixels(pixels).expect("Passed empty color vector to test");
+
+        assert_eq!(bucket.colors, vec![Color { r: 10, g: 10, b: 10, a: 255 }, Color { r: 200, g: 0, b: 0, a: 255 }]);
+        assert_eq!(bucket.counts, vec![2, 1]);
+    }
+
+    #[test]
+    fn channel_mean_weights_by_count_ut() {
+        let pixels = vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 100, g: 0, b: 0, a: 255 }];
+        let bucket: ColorBucket = ColorBucket::from_pixels(pixels).expect("Passed empty color vector to test");
+
+        assert_eq!(25, bucket.channel_mean(ColorChannel::R));
     }
 
     fn generate_unsorted_colors() -> Vec<Color> {
@@ -1,7 +1,10 @@
 use std::fs::File;
 use std::io::Write;
 
+use image::{GenericImageView, ImageBuffer, Rgba};
+
 use iris_lib::color::Color;
+use iris_lib::palette::Palette;
 
 /// Writes a html file containing the target image and the according colors.
 ///
@@ -114,3 +117,133 @@ pub fn write_csv_out(color_data: &[Color], out_file_path: &str) -> std::io::Resu
     file.write_all(csv.as_bytes())?;
     Ok(())
 }
+
+/// Writes a color palette to a GIMP `.gpl` palette file.
+///
+/// # Arguments
+///
+/// * `color_data` - Colors to be written to the gpl file.
+/// * `out_file_path` - Path the output file should be written to.
+///
+/// # Examples
+///
+/// ```
+/// let colors = vec![Color {r: 255, g: 0, b: 0, a: 255}];
+/// write_gpl_out(&colors, "palette")?;
+/// ```
+///
+pub fn write_gpl_out(color_data: &[Color], out_file_path: &str) -> std::io::Result<()> {
+    let mut gpl: String = String::new();
+
+    gpl.push_str("GIMP Palette\n");
+    gpl.push_str(format!("Name: {}\n", out_file_path).as_str());
+    gpl.push_str("#\n");
+
+    for (index, color) in color_data.iter().enumerate() {
+        gpl.push_str(format!("{:>3} {:>3} {:>3}\tColor {}\n", color.r, color.g, color.b, index + 1).as_str());
+    }
+
+    let mut file = File::create(format!("{}.gpl", out_file_path))?;
+    file.write_all(gpl.as_bytes())?;
+    Ok(())
+}
+
+/// Writes a color palette as a newline separated list of `#RRGGBB` hex codes, e.g. for use as a
+/// terminal color scheme.
+///
+/// # Arguments
+///
+/// * `color_data` - Colors to be written as hex codes.
+/// * `out_file_path` - Path the output file should be written to.
+///
+/// # Examples
+///
+/// ```
+/// let colors = vec![Color {r: 255, g: 0, b: 0, a: 255}];
+/// write_hex_out(&colors, "palette")?;
+/// ```
+///
+pub fn write_hex_out(color_data: &[Color], out_file_path: &str) -> std::io::Result<()> {
+    let mut hex: String = String::new();
+
+    for color in color_data {
+        hex.push_str(format!("#{:02X}{:02X}{:02X}\n", color.r, color.g, color.b).as_str());
+    }
+
+    let mut file = File::create(format!("{}.hex", out_file_path))?;
+    file.write_all(hex.as_bytes())?;
+    Ok(())
+}
+
+/// Prints each color as a true-color ANSI background block so a palette can be eyeballed in a
+/// terminal without opening an output file.
+///
+/// # Arguments
+///
+/// * `color_data` - Colors to be previewed.
+///
+/// # Examples
+///
+/// ```
+/// let colors = vec![Color {r: 255, g: 0, b: 0, a: 255}];
+/// print_ansi_preview(&colors);
+/// ```
+///
+pub fn print_ansi_preview(color_data: &[Color]) {
+    for color in color_data {
+        print!("\x1b[48;2;{};{};{}m  \x1b[0m", color.r, color.g, color.b);
+    }
+    println!();
+}
+
+/// Re-renders `image_file_path` using only the colors in `palette`, mapping every pixel to its
+/// closest palette entry, and writes both the resulting quantized image and a pixel -> palette
+/// index map.
+///
+/// # Arguments
+///
+/// * `image_file_path` - Source image to be quantized.
+/// * `palette` - Palette the output image's colors are restricted to.
+/// * `out_file_path` - Path the quantized image and index map should be written to.
+///
+/// # Examples
+///
+/// ```
+/// let palette = Palette::new(vec![Color { r: 255, g: 0, b: 0, a: 255 }]);
+/// write_indexed_out("example.jpg", &palette, "palette")?;
+/// ```
+///
+pub fn write_indexed_out(image_file_path: &str, palette: &Palette, out_file_path: &str) -> std::io::Result<()> {
+    if palette.colors().is_empty() {
+        return Err(std::io::Error::new(std::io::ErrorKind::InvalidInput, "Palette must not be empty"));
+    }
+
+    let image = image::open(image_file_path).map_err(|err| std::io::Error::new(std::io::ErrorKind::InvalidData, err))?;
+    let (width, height) = image.dimensions();
+    let source = image.to_rgba8();
+
+    let mut quantized = ImageBuffer::<Rgba<u8>, Vec<u8>>::new(width, height);
+    let mut index_map = String::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let pixel = source.get_pixel(x, y);
+            let color = Color { r: pixel[0], g: pixel[1], b: pixel[2], a: pixel[3] };
+            let index = palette.closest_index(color);
+            let closest = palette.colors()[index];
+
+            quantized.put_pixel(x, y, Rgba([closest.r, closest.g, closest.b, closest.a]));
+
+            index_map.push_str(&index.to_string());
+            index_map.push(if x + 1 < width { ',' } else { '\n' });
+        }
+    }
+
+    quantized
+        .save(format!("{}_quantized.png", out_file_path))
+        .map_err(|err| std::io::Error::new(std::io::ErrorKind::Other, err))?;
+
+    let mut file = File::create(format!("{}_index_map.csv", out_file_path))?;
+    file.write_all(index_map.as_bytes())?;
+    Ok(())
+}
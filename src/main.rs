@@ -1,6 +1,7 @@
 extern crate iris_lib;
 
-use iris_lib::color_bucket::ColorBucket;
+use iris_lib::color_bucket::AnyColorBucket;
+use iris_lib::palette::Palette;
 use std::time::Instant;
 
 use crate::export::*;
@@ -21,6 +22,11 @@ struct Args {
     #[clap(short, long, default_value_t = 1)]
     iterations: u8,
 
+    /// Desired number of colors in the resulting palette. Overrides `--iterations` when set and
+    /// is not limited to powers of two.
+    #[clap(short, long)]
+    colors: Option<usize>,
+
     /// Desired data file format to be written.
     #[clap(arg_enum, default_value_t = OutputFormat::None)]
     output_format: OutputFormat,
@@ -28,6 +34,15 @@ struct Args {
     /// File path the file should be written to.
     #[clap(short, long, default_value_t = String::from("palette"))]
     out_filename: String,
+
+    /// Print each palette color as an ANSI true-color swatch to the terminal.
+    #[clap(long)]
+    preview: bool,
+
+    /// Expands the palette into a gradient before output, inserting this many interpolated
+    /// colors between each pair of consecutive palette entries.
+    #[clap(long)]
+    gradient: Option<usize>,
 }
 
 /// Represents all possible file output formats for color palettes.
@@ -37,6 +52,9 @@ enum OutputFormat {
     Html,
     Json,
     Csv,
+    Indexed,
+    Gpl,
+    Hex,
 }
 
 fn main() {
@@ -57,14 +75,26 @@ fn main() {
     let now = Instant::now();
     println!("Generating palette...");
 
-    if let Some(mut color_bucket) = ColorBucket::from_image(&args.file_name) {
-        let palette = color_bucket.make_palette(num_iterations);
+    if let Some(mut color_bucket) = AnyColorBucket::from_image(&args.file_name) {
+        let palette = match args.colors {
+            Some(num_colors) => color_bucket.make_palette_sized(num_colors),
+            None => color_bucket.make_palette(num_iterations),
+        };
         println!("Finished generating palette in {} ms.\n", now.elapsed().as_millis());
 
+        let palette = match args.gradient {
+            Some(steps) => Palette::new(palette).gradient(steps),
+            None => palette,
+        };
+
         for color in &palette {
             println!("{}", color);
         }
 
+        if args.preview {
+            print_ansi_preview(&palette);
+        }
+
         match args.output_format {
             OutputFormat::Html => match write_html_out(&args.file_name, &palette, &args.out_filename) {
                 Ok(_) => (),
@@ -78,6 +108,18 @@ fn main() {
                 Ok(_) => (),
                 Err(err) => println!("Failed writing csv output file:\n{}", err),
             },
+            OutputFormat::Indexed => match write_indexed_out(&args.file_name, &Palette::new(palette.clone()), &args.out_filename) {
+                Ok(_) => (),
+                Err(err) => println!("Failed writing indexed output file:\n{}", err),
+            },
+            OutputFormat::Gpl => match write_gpl_out(&palette, &args.out_filename) {
+                Ok(_) => (),
+                Err(err) => println!("Failed writing gpl output file:\n{}", err),
+            },
+            OutputFormat::Hex => match write_hex_out(&palette, &args.out_filename) {
+                Ok(_) => (),
+                Err(err) => println!("Failed writing hex output file:\n{}", err),
+            },
             OutputFormat::None => (),
         }
     }
@@ -0,0 +1,170 @@
+use crate::color::Color;
+
+/// Wraps the `Vec<Color>` produced by the median cut algorithm and adds lookup helpers for
+/// consumers that want to map arbitrary colors onto the closest palette entry, e.g. when
+/// rendering a quantized/indexed version of the source image.
+///
+#[derive(Debug, Clone, PartialEq)]
+pub struct Palette {
+    colors: Vec<Color>,
+}
+
+impl Palette {
+    /// Creates a `Palette` wrapping the passed colors.
+    ///
+    /// # Arguments
+    ///
+    /// * `colors` - Palette colors, typically the output of `ColorBucket::make_palette`.
+    ///
+    pub fn new(colors: Vec<Color>) -> Self {
+        Self { colors }
+    }
+
+    /// Returns the palette's colors.
+    ///
+    pub fn colors(&self) -> &[Color] {
+        &self.colors
+    }
+
+    /// Returns the palette entry minimizing squared Euclidean distance to `target` over R/G/B.
+    /// Ignores alpha, consistent with `ColorBucket::highest_variance_channel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Color to find the closest palette entry for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the palette is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let palette = Palette::new(vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]);
+    /// let closest = palette.closest_color(Color { r: 200, g: 200, b: 200, a: 255 });
+    /// assert_eq!(closest, Color { r: 255, g: 255, b: 255, a: 255 });
+    /// ```
+    ///
+    pub fn closest_color(&self, target: Color) -> Color {
+        self.colors[self.closest_index(target)]
+    }
+
+    /// Returns the index of the palette entry minimizing squared Euclidean distance to `target`
+    /// over R/G/B. Ignores alpha, consistent with `ColorBucket::highest_variance_channel`.
+    ///
+    /// # Arguments
+    ///
+    /// * `target` - Color to find the closest palette entry for.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the palette is empty.
+    ///
+    pub fn closest_index(&self, target: Color) -> usize {
+        self.colors
+            .iter()
+            .enumerate()
+            .min_by_key(|(_, color)| Self::squared_distance(**color, target))
+            .map(|(index, _)| index)
+            .expect("Palette must not be empty")
+    }
+
+    /// Returns the squared Euclidean distance between two colors over R/G/B, ignoring alpha.
+    ///
+    fn squared_distance(a: Color, b: Color) -> u32 {
+        let dr = a.r as i32 - b.r as i32;
+        let dg = a.g as i32 - b.g as i32;
+        let db = a.b as i32 - b.b as i32;
+        (dr * dr + dg * dg + db * db) as u32
+    }
+
+    /// Expands the palette into a gradient, linearly interpolating `steps` intermediate colors
+    /// between each pair of consecutive palette entries, so the palette can be used as a
+    /// ramp/shading gradient rather than just a flat swatch list. Palettes with fewer than two
+    /// colors are returned unchanged, since there is nothing to interpolate between.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - number of intermediate colors to insert between each pair of palette entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let palette = Palette::new(vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]);
+    /// let gradient = palette.gradient(1);
+    /// assert_eq!(gradient, vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 128, g: 128, b: 128, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]);
+    /// ```
+    ///
+    pub fn gradient(&self, steps: usize) -> Vec<Color> {
+        if self.colors.len() < 2 {
+            return self.colors.clone();
+        }
+
+        let mut result = vec![];
+        for window in self.colors.windows(2) {
+            let (start, end) = (window[0], window[1]);
+            result.push(start);
+            for step in 1..=steps {
+                let t = step as f32 / (steps + 1) as f32;
+                result.push(Color::lerp(start, end, t));
+            }
+        }
+        result.push(*self.colors.last().expect("Palette must not be empty"));
+
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closest_color_ut() {
+        let palette = Palette::new(vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]);
+
+        assert_eq!(palette.closest_color(Color { r: 10, g: 5, b: 0, a: 0 }), Color { r: 0, g: 0, b: 0, a: 255 });
+        assert_eq!(palette.closest_color(Color { r: 200, g: 210, b: 255, a: 0 }), Color { r: 255, g: 255, b: 255, a: 255 });
+    }
+
+    #[test]
+    fn closest_color_ignores_alpha_ut() {
+        let palette = Palette::new(vec![Color { r: 10, g: 10, b: 10, a: 0 }]);
+        assert_eq!(palette.closest_color(Color { r: 10, g: 10, b: 10, a: 255 }), Color { r: 10, g: 10, b: 10, a: 0 });
+    }
+
+    #[test]
+    fn closest_index_ut() {
+        let palette = Palette::new(vec![
+            Color { r: 0, g: 0, b: 0, a: 255 },
+            Color { r: 128, g: 128, b: 128, a: 255 },
+            Color { r: 255, g: 255, b: 255, a: 255 },
+        ]);
+
+        assert_eq!(palette.closest_index(Color { r: 100, g: 140, b: 130, a: 255 }), 1);
+    }
+
+    #[test]
+    fn gradient_ut() {
+        let palette = Palette::new(vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]);
+
+        assert_eq!(
+            palette.gradient(1),
+            vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 128, g: 128, b: 128, a: 255 }, Color { r: 255, g: 255, b: 255, a: 255 }]
+        );
+    }
+
+    #[test]
+    fn gradient_with_multiple_segments_ut() {
+        let palette = Palette::new(vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 100, g: 0, b: 0, a: 255 }, Color { r: 200, g: 0, b: 0, a: 255 }]);
+
+        let result = palette.gradient(0);
+        assert_eq!(result, vec![Color { r: 0, g: 0, b: 0, a: 255 }, Color { r: 100, g: 0, b: 0, a: 255 }, Color { r: 200, g: 0, b: 0, a: 255 }]);
+    }
+
+    #[test]
+    fn gradient_single_color_is_unchanged_ut() {
+        let palette = Palette::new(vec![Color { r: 10, g: 20, b: 30, a: 255 }]);
+        assert_eq!(palette.gradient(4), vec![Color { r: 10, g: 20, b: 30, a: 255 }]);
+    }
+}
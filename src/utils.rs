@@ -1,15 +1,56 @@
-/// Return the mean value for a `Iterator<u8>`.
+use crate::color::Sample;
+
+/// Return the mean value for a `Iterator<T>` of `Sample`s. Arithmetic is done through `T::as_f64`
+/// so this works uniformly across `u8`, `u16` and `f32` channels.
 ///
 /// # Examples
-/// 
+///
 /// ```
 /// let data: Vec<u8> = vec![33, 13, 255, 0, 42];
 /// let result = mean(data.into_iter());
 /// assert_eq!(68, result);
 /// ```
-/// 
-pub fn mean(iter: impl Iterator<Item = u8> + Clone) -> u8 {
-    (iter.clone().map(|x| x as u64).sum::<u64>() / iter.count() as u64) as u8
+///
+pub fn mean<T: Sample>(iter: impl Iterator<Item = T> + Clone) -> T {
+    let count = iter.clone().count() as f64;
+    let sum: f64 = iter.map(|x| x.as_f64()).sum();
+    T::from_f64(sum / count)
+}
+
+/// Returns the population-weighted mean for an iterator of `(value, count)` pairs, i.e.
+/// `Σ(value·count) / Σcount`. Equivalent to `mean` over the raw (non-deduplicated) samples, but
+/// lets callers pass already-deduplicated `(value, count)` pairs instead of repeating `value`
+/// `count` times.
+///
+/// # Examples
+///
+/// ```
+/// let pairs: Vec<(u8, u32)> = vec![(10, 1), (20, 3)];
+/// let result = weighted_mean(pairs.into_iter());
+/// assert_eq!(17, result);
+/// ```
+///
+pub fn weighted_mean<T: Sample>(pairs: impl Iterator<Item = (T, u32)> + Clone) -> T {
+    T::from_f64(weighted_mean_f64(pairs))
+}
+
+/// Returns the population-weighted mean for an iterator of `(value, count)` pairs as an `f64`,
+/// i.e. `Σ(value·count) / Σcount`, without rounding through `T`. Useful for callers (e.g.
+/// variance) that need the exact mean rather than the `Sample`-rounded one `weighted_mean`
+/// returns.
+///
+/// # Examples
+///
+/// ```
+/// let pairs: Vec<(u8, u32)> = vec![(10, 1), (20, 3)];
+/// let result = weighted_mean_f64(pairs.into_iter());
+/// assert_eq!(17.5, result);
+/// ```
+///
+pub fn weighted_mean_f64<T: Sample>(pairs: impl Iterator<Item = (T, u32)> + Clone) -> f64 {
+    let total: f64 = pairs.clone().map(|(_, count)| count as f64).sum();
+    let weighted_sum: f64 = pairs.map(|(value, count)| value.as_f64() * count as f64).sum();
+    weighted_sum / total
 }
 
 #[cfg(test)]
@@ -22,4 +63,32 @@ mod tests {
         let result = mean(data.into_iter());
         assert_eq!(68, result);
     }
+
+    #[test]
+    fn mean_f32_ut() {
+        let data: Vec<f32> = vec![0.0, 0.5, 1.0];
+        let result = mean(data.into_iter());
+        assert_eq!(0.5, result);
+    }
+
+    #[test]
+    fn weighted_mean_ut() {
+        let pairs: Vec<(u8, u32)> = vec![(10, 1), (20, 3)];
+        let result = weighted_mean(pairs.into_iter());
+        assert_eq!(17, result);
+    }
+
+    #[test]
+    fn weighted_mean_matches_mean_when_uncounted_ut() {
+        let data: Vec<u8> = vec![33, 13, 255, 0, 42];
+        let pairs = data.iter().map(|&value| (value, 1));
+        assert_eq!(mean(data.clone().into_iter()), weighted_mean(pairs));
+    }
+
+    #[test]
+    fn weighted_mean_f64_keeps_fractional_precision_ut() {
+        let pairs: Vec<(u8, u32)> = vec![(10, 1), (21, 3)];
+        let result = weighted_mean_f64(pairs.into_iter());
+        assert_eq!(18.25, result);
+    }
 }